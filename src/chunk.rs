@@ -1,107 +1,246 @@
 #[allow(unused_imports)]
 use crate::chunk_type::ChunkType;
-use crc::{Crc, CRC_32_CKSUM, CRC_32_ISCSI, CRC_32_MPEG_2};
+use bytes::{Buf, Bytes};
+use crc::{Crc, CRC_32_ISO_HDLC};
 use std::fmt::{Display, Formatter};
-use std::str::FromStr;
+
+// Errors raised while decoding a chunk from its on-disk byte form. Modelled on
+// the layered decode errors in the `der` crate: one variant per thing that can
+// go wrong so callers can match instead of guessing.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ChunkError {
+    /// The buffer ends before a complete chunk (length + type + data + crc), or
+    /// a whole-file buffer is shorter than the 8-byte signature.
+    TooShort,
+    /// The buffer length does not match the declared data length.
+    LengthMismatch { declared: usize, actual: usize },
+    /// The four type bytes are not a valid `ChunkType`.
+    InvalidChunkType([u8; 4]),
+    /// The trailing CRC does not match the CRC recomputed over type + data.
+    CrcMismatch { expected: u32, found: u32 },
+    /// The leading 8 bytes are not the PNG signature.
+    InvalidSignature([u8; 8]),
+    /// The chunk data is not valid UTF-8 when read as text.
+    Utf8(std::string::FromUtf8Error),
+}
+
+impl Display for ChunkError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChunkError::TooShort => write!(f, "chunk buffer is too short"),
+            ChunkError::LengthMismatch { declared, actual } => {
+                write!(f, "length mismatch: declared {declared} bytes, got {actual}")
+            }
+            ChunkError::InvalidChunkType(bytes) => {
+                write!(f, "invalid chunk type {bytes:?}")
+            }
+            ChunkError::CrcMismatch { expected, found } => {
+                write!(f, "crc mismatch: expected {expected}, found {found}")
+            }
+            ChunkError::InvalidSignature(bytes) => {
+                write!(f, "invalid png signature {bytes:?}")
+            }
+            ChunkError::Utf8(err) => write!(f, "chunk data is not valid utf-8: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ChunkError {}
 
 // The first eight bytes of a PNG file always contain the following (decimal) values:
 // 137 80 78 71 13 10 26 10
 
 #[allow(unused_variables)]
-struct Chunk {
+pub struct Chunk {
     len: u32,
     chuck_type: ChunkType,
-    chunk_data: Vec<u8>,
+    chunk_data: Bytes,
     crc: u32,
 }
 #[allow(unused_variables)]
 #[allow(dead_code)]
 impl Chunk {
-    fn new(chunk_type: ChunkType, data: Vec<u8>) -> Chunk {
-        let mut trimmed_data = data;
-        while let Some(b) = trimmed_data.last() {
-            if b.is_ascii_graphic() {
-                break;
-            }
-            trimmed_data.pop();
-        }
+    pub fn new(chunk_type: ChunkType, data: Vec<u8>) -> Chunk {
         let mut ret = Chunk {
-            len: trimmed_data.len() as u32,
+            len: data.len() as u32,
             chuck_type: chunk_type,
-            chunk_data: trimmed_data,
+            chunk_data: Bytes::from(data),
             crc: 0,
         };
         ret.crc = ret.crc();
         ret
     }
-    fn length(&self) -> u32 {
+    pub fn length(&self) -> u32 {
         self.len
     }
-    fn chunk_type(&self) -> &ChunkType {
+    pub fn chunk_type(&self) -> &ChunkType {
         &self.chuck_type
     }
-    fn data(&self) -> &[u8] {
-        self.chunk_data.as_slice()
+    pub fn data(&self) -> &[u8] {
+        self.chunk_data.as_ref()
+    }
+
+    // Decodes a single chunk from anything implementing `bytes::Buf`, reading
+    // the length, type and CRC with `get_u32` and slicing the payload out with
+    // `copy_to_bytes`; when the source is `Bytes` the payload is a refcounted
+    // view into the original buffer rather than a fresh allocation, while other
+    // `Buf` types copy it. `TooShort` is returned whenever the buffer does not
+    // hold a full chunk. If the length and type are present but the payload or
+    // CRC is not, those first 8 bytes have already been consumed from `buf`.
+    pub fn read_from<B: Buf>(buf: &mut B) -> Result<Chunk, ChunkError> {
+        // length(4) + type(4) must be present before we touch the payload.
+        if buf.remaining() < 8 {
+            return Err(ChunkError::TooShort);
+        }
+        let len = buf.get_u32();
+        let type_bytes = buf.get_u32().to_be_bytes();
+        let chuck_type = ChunkType::try_from(type_bytes)
+            .map_err(|_| ChunkError::InvalidChunkType(type_bytes))?;
+
+        // data(len) + crc(4) must also be buffered.
+        if buf.remaining() < len as usize + 4 {
+            return Err(ChunkError::TooShort);
+        }
+        let chunk_data = buf.copy_to_bytes(len as usize);
+        let found = buf.get_u32();
+
+        let ret = Chunk {
+            len,
+            chuck_type,
+            chunk_data,
+            crc: found,
+        };
+        let expected = ret.crc();
+        if expected != found {
+            return Err(ChunkError::CrcMismatch { expected, found });
+        }
+        Ok(ret)
     }
     // Don't forget to include the chunk type in your CRC calculation.
-    fn crc(&self) -> u32 {
-        const CHECKSUM_U32: Crc<u32> = Crc::<u32>::new(&CRC_32_CKSUM);
+    pub fn crc(&self) -> u32 {
+        const CHECKSUM_U32: Crc<u32> = Crc::<u32>::new(&CRC_32_ISO_HDLC);
         let chunk_type = self.chuck_type.to_string();
         let chunk_type_bytes = Vec::from(chunk_type.as_bytes());
-        let data = self.chunk_data.as_slice();
+        let data = self.chunk_data.as_ref();
         let mut combined: Vec<u8> = chunk_type_bytes;
         combined.extend_from_slice(data);
-        let combined_slice = combined.as_slice();
-        println!("WAT{:?}", combined_slice);
-        CHECKSUM_U32.checksum(combined_slice)
+        CHECKSUM_U32.checksum(combined.as_slice())
+    }
+
+    pub fn data_as_string(&self) -> Result<String, ChunkError> {
+        String::from_utf8(self.chunk_data.to_vec()).map_err(ChunkError::Utf8)
     }
 
-    fn data_as_string(&self) -> Result<String, String> {
-        println!("data_as_string{:?}", self.chunk_data.as_slice());
-        let ret: String = self
-            .chunk_data
+    pub fn as_bytes(&self) -> Vec<u8> {
+        self.len
+            .to_be_bytes()
             .iter()
-            .filter(|&x| x.is_ascii_alphanumeric() || *x == b' ')
-            .map(|&x| x as char)
-            .collect();
-        // let temp = self.chunk_data.clone();
-        // let hear = String::from_utf8(temp).unwrap();
-        Ok(ret)
+            .chain(self.chuck_type.to_string().as_bytes().iter())
+            .chain(self.chunk_data.iter())
+            .chain(self.crc.to_be_bytes().iter())
+            .copied()
+            .collect()
+    }
+}
+
+// The first eight bytes of every PNG file.
+pub const PNG_SIGNATURE: [u8; 8] = [137, 80, 78, 71, 13, 10, 26, 10];
+
+// A streaming serializer for assembling a full PNG byte sequence one chunk at a
+// time, in the spirit of the RLP crate's `RlpStream`: `new()` seeds the buffer
+// with the signature, `append` pushes one framed chunk, and `out()` hands back
+// the finished buffer.
+#[allow(dead_code)]
+pub struct ChunkStream {
+    buf: Vec<u8>,
+}
+
+#[allow(dead_code)]
+impl ChunkStream {
+    pub fn new() -> ChunkStream {
+        ChunkStream {
+            buf: PNG_SIGNATURE.to_vec(),
+        }
+    }
+
+    // Writes `length || type || data || crc` in network byte order, recomputing
+    // the CRC over type + data as the chunk is appended. Returns `&mut self` so
+    // appends can be chained.
+    pub fn append(&mut self, chunk: &Chunk) -> &mut ChunkStream {
+        let type_bytes = chunk.chuck_type.to_string().into_bytes();
+        let data = chunk.data();
+        let crc = chunk.crc();
+
+        self.buf
+            .extend_from_slice(&(data.len() as u32).to_be_bytes());
+        self.buf.extend_from_slice(&type_bytes);
+        self.buf.extend_from_slice(data);
+        self.buf.extend_from_slice(&crc.to_be_bytes());
+        self
+    }
+
+    pub fn out(self) -> Vec<u8> {
+        self.buf
     }
+}
 
-    fn as_bytes(&self) -> Vec<u8> {
-        self.chunk_data.to_vec()
+impl Default for ChunkStream {
+    fn default() -> Self {
+        ChunkStream::new()
     }
 }
 
 impl TryFrom<&[u8]> for Chunk {
-    type Error = ();
+    type Error = ChunkError;
 
     fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
-        // println!("value {:?}", String::from_utf8(value.to_vec()));
-        const CHECKSUM_U32: Crc<u32> = Crc::<u32>::new(&CRC_32_CKSUM);
-        let mut bytes: [u8; 4] = [0, 0, 0, 0];
-        bytes[..4].copy_from_slice(&value[..4]);
-        //for i in 0..4{
-        //    bytes[i] = s.as_bytes()[i];
-        //}
-        let slice_end = value.len() - 4;
-        let val_str: String = value[0..slice_end].iter().map(|x| *x as char).collect();
-        println!("val_str {}", val_str);
-        let ret: Chunk = Chunk {
-            len: u32::from_be_bytes(bytes),
-            chuck_type: ChunkType::from_str(&val_str[4..8]).unwrap(),
-            chunk_data: value[8..].to_vec(),
-            crc: CHECKSUM_U32.checksum(value),
+        // Framing is length(4) || type(4) || data(length) || crc(4).
+        if value.len() < 12 {
+            return Err(ChunkError::TooShort);
+        }
+
+        let mut len_bytes: [u8; 4] = [0, 0, 0, 0];
+        len_bytes.copy_from_slice(&value[..4]);
+        let len = u32::from_be_bytes(len_bytes);
+
+        let declared = 12 + len as usize;
+        if value.len() != declared {
+            return Err(ChunkError::LengthMismatch {
+                declared,
+                actual: value.len(),
+            });
+        }
+
+        let mut type_bytes: [u8; 4] = [0, 0, 0, 0];
+        type_bytes.copy_from_slice(&value[4..8]);
+        let chuck_type =
+            ChunkType::try_from(type_bytes).map_err(|_| ChunkError::InvalidChunkType(type_bytes))?;
+
+        let data_end = 8 + len as usize;
+        let chunk_data = Bytes::copy_from_slice(&value[8..data_end]);
+
+        let mut crc_bytes: [u8; 4] = [0, 0, 0, 0];
+        crc_bytes.copy_from_slice(&value[data_end..]);
+        let found = u32::from_be_bytes(crc_bytes);
+
+        let ret = Chunk {
+            len,
+            chuck_type,
+            chunk_data,
+            crc: found,
         };
+        let expected = ret.crc();
+        if expected != found {
+            return Err(ChunkError::CrcMismatch { expected, found });
+        }
 
         Ok(ret)
     }
 }
 
 impl Display for Chunk {
-    fn fmt(&self, _f: &mut Formatter<'_>) -> std::fmt::Result {
-        todo!()
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({} bytes)", self.chuck_type, self.len)
     }
 }
 
@@ -1,5 +1,6 @@
 // https://picklenerd.github.io/pngme_book/chapter_1.html
 
+use crate::chunk::ChunkError;
 use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 
@@ -22,53 +23,53 @@ impl ChunkType {
 
     // Ancillary bit: bit 5 of first byte
     //      0 (uppercase) = critical, 1 (lowercase) = ancillary.
-    fn is_critical(&self) -> bool {
+    pub fn is_critical(&self) -> bool {
         self.bytes()[0] & 0b0001_0000 == 0
     }
 
     //Private bit: bit 5 of second byte
     //     0 (uppercase) = public, 1 (lowercase) = private.
-    fn is_public(&self) -> bool {
+    pub fn is_public(&self) -> bool {
         self.bytes()[1] & 0b0001_0000 == 0
     }
     //Reserved bit: bit 5 of third byte
     //     Must be 0 (uppercase) in files conforming to this version of PNG.
-    fn is_reserved_bit_valid(&self) -> bool {
+    pub fn is_reserved_bit_valid(&self) -> bool {
         self.bytes()[2] & 0b0001_0000 == 0
     }
     //Safe-to-copy bit: bit 5 of fourth byte
     //     0 (uppercase) = unsafe to copy, 1 (lowercase) = safe to copy.
-    fn is_safe_to_copy(&self) -> bool {
+    pub fn is_safe_to_copy(&self) -> bool {
         self.bytes()[3] & 0b0001_0000 == 0
     }
 }
 
 impl TryFrom<[u8; 4]> for ChunkType {
-    type Error = ();
+    type Error = ChunkError;
 
     fn try_from(value: [u8; 4]) -> Result<Self, Self::Error> {
-        let mut ret: ChunkType = ChunkType { chunk_type_code: 0 };
-        ret.chunk_type_code = u32::from_be_bytes(value);
-        Ok(ret)
+        let ret = ChunkType {
+            chunk_type_code: u32::from_be_bytes(value),
+        };
+        if ret.is_valid() {
+            Ok(ret)
+        } else {
+            Err(ChunkError::InvalidChunkType(value))
+        }
     }
 }
 impl FromStr for ChunkType {
-    type Err = ();
+    type Err = ChunkError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut ret: ChunkType = ChunkType { chunk_type_code: 0 };
+        let raw = s.as_bytes();
         let mut bytes: [u8; 4] = [0, 0, 0, 0];
-        //
-        //for i in 0..4{
-        //    bytes[i] = s.as_bytes()[i];
-        //}
-        bytes[..4].copy_from_slice(&s.as_bytes()[..4]);
-        ret.chunk_type_code = u32::from_be_bytes(bytes);
-        if ret.is_valid() {
-            Ok(ret)
-        } else {
-            Err(())
+        let n = raw.len().min(4);
+        bytes[..n].copy_from_slice(&raw[..n]);
+        if raw.len() != 4 {
+            return Err(ChunkError::InvalidChunkType(bytes));
         }
+        ChunkType::try_from(bytes)
     }
 }
 
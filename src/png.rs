@@ -0,0 +1,204 @@
+// https://picklenerd.github.io/pngme_book/chapter_3.html
+//
+// A `Png` is the whole-file layer sitting on top of `Chunk`/`ChunkType`: the
+// 8-byte signature followed by a sequence of chunks. It is also where the
+// crate earns its name, hiding and recovering messages in ancillary chunks.
+
+use crate::chunk::{Chunk, ChunkError, ChunkStream, PNG_SIGNATURE};
+use crate::chunk_type::ChunkType;
+use bytes::{Buf, Bytes};
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use std::str::FromStr;
+
+#[allow(dead_code)]
+pub struct Png {
+    chunks: Vec<Chunk>,
+}
+
+#[allow(dead_code)]
+impl Png {
+    // Decodes a PNG from memory: checks the signature, then reads chunks one at
+    // a time off a `Bytes` cursor using the zero-copy `Chunk::read_from` path.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Png, ChunkError> {
+        if bytes.len() < 8 {
+            return Err(ChunkError::TooShort);
+        }
+        let mut signature = [0u8; 8];
+        signature.copy_from_slice(&bytes[..8]);
+        if signature != PNG_SIGNATURE {
+            return Err(ChunkError::InvalidSignature(signature));
+        }
+
+        let mut buf = Bytes::copy_from_slice(&bytes[8..]);
+        let mut chunks = Vec::new();
+        while buf.has_remaining() {
+            chunks.push(Chunk::read_from(&mut buf)?);
+        }
+        Ok(Png { chunks })
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Png, Box<dyn std::error::Error>> {
+        let bytes = std::fs::read(path)?;
+        Ok(Png::from_bytes(&bytes)?)
+    }
+
+    pub fn write_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::write(path, self.as_bytes())?;
+        Ok(())
+    }
+
+    pub fn chunks(&self) -> &[Chunk] {
+        &self.chunks
+    }
+
+    pub fn append_chunk(&mut self, chunk: Chunk) {
+        self.chunks.push(chunk);
+    }
+
+    // Removes and returns the first chunk whose type matches, or `None` if there
+    // is no such chunk.
+    pub fn remove_first_chunk(&mut self, chunk_type: &str) -> Option<Chunk> {
+        let index = self
+            .chunks
+            .iter()
+            .position(|c| c.chunk_type().to_string() == chunk_type)?;
+        Some(self.chunks.remove(index))
+    }
+
+    pub fn chunk_by_type(&self, chunk_type: &str) -> Option<&Chunk> {
+        self.chunks
+            .iter()
+            .find(|c| c.chunk_type().to_string() == chunk_type)
+    }
+
+    // Re-emits the signature followed by every chunk, reusing `ChunkStream` so
+    // the framing stays identical to freshly built streams.
+    pub fn as_bytes(&self) -> Vec<u8> {
+        let mut stream = ChunkStream::new();
+        for chunk in &self.chunks {
+            stream.append(chunk);
+        }
+        stream.out()
+    }
+
+    // Hides `message` in a new chunk of the given type. The type is expected to
+    // be a private, ancillary code so decoders leave it untouched.
+    pub fn encode_message(&mut self, chunk_type: &str, message: &str) -> Result<(), ChunkError> {
+        let parsed = ChunkType::from_str(chunk_type)?;
+        // Bit 5 (0x20, lowercase) of the first byte marks ancillary and of the
+        // second byte marks private; both must be set.
+        let raw = chunk_type.as_bytes();
+        if raw[0] & 0b0010_0000 == 0 || raw[1] & 0b0010_0000 == 0 {
+            let mut bytes = [0u8; 4];
+            bytes.copy_from_slice(chunk_type.as_bytes());
+            return Err(ChunkError::InvalidChunkType(bytes));
+        }
+        let chunk = Chunk::new(parsed, message.as_bytes().to_vec());
+        self.append_chunk(chunk);
+        Ok(())
+    }
+
+    // Reads back a message previously stored under `chunk_type`, if present and
+    // valid UTF-8.
+    pub fn decode_message(&self, chunk_type: &str) -> Option<String> {
+        self.chunk_by_type(chunk_type)
+            .and_then(|chunk| chunk.data_as_string().ok())
+    }
+}
+
+impl Display for Png {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for chunk in &self.chunks {
+            writeln!(f, "{}", chunk.chunk_type())?;
+        }
+        Ok(())
+    }
+}
+
+#[allow(unused_variables)]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk::Chunk;
+    use crate::chunk_type::ChunkType;
+    use std::str::FromStr;
+
+    fn chunk_from_strings(chunk_type: &str, data: &str) -> Chunk {
+        let chunk_type = ChunkType::from_str(chunk_type).unwrap();
+        let data: Vec<u8> = data.bytes().collect();
+        Chunk::new(chunk_type, data)
+    }
+
+    fn testing_chunks() -> Vec<Chunk> {
+        vec![
+            chunk_from_strings("FrSt", "I am the first chunk"),
+            chunk_from_strings("miDl", "I am another chunk"),
+            chunk_from_strings("LASt", "I am the last chunk"),
+        ]
+    }
+
+    fn testing_png() -> Png {
+        Png {
+            chunks: testing_chunks(),
+        }
+    }
+
+    #[test]
+    fn test_from_bytes() {
+        let png = testing_png();
+        let bytes = png.as_bytes();
+        let decoded = Png::from_bytes(bytes.as_ref()).unwrap();
+        assert_eq!(decoded.chunks().len(), 3);
+    }
+
+    #[test]
+    fn test_invalid_signature() {
+        let mut bytes = testing_png().as_bytes();
+        bytes[0] = 0;
+        assert!(Png::from_bytes(bytes.as_ref()).is_err());
+    }
+
+    #[test]
+    fn test_append_chunk() {
+        let mut png = testing_png();
+        png.append_chunk(chunk_from_strings("TeSt", "Message"));
+        assert!(png.chunk_by_type("TeSt").is_some());
+    }
+
+    #[test]
+    fn test_remove_first_chunk() {
+        let mut png = testing_png();
+        let removed = png.remove_first_chunk("miDl");
+        assert!(removed.is_some());
+        assert!(png.chunk_by_type("miDl").is_none());
+    }
+
+    #[test]
+    fn test_remove_missing_chunk() {
+        let mut png = testing_png();
+        assert!(png.remove_first_chunk("nOpE").is_none());
+    }
+
+    #[test]
+    fn test_chunk_by_type() {
+        let png = testing_png();
+        let chunk = png.chunk_by_type("FrSt").unwrap();
+        assert_eq!(chunk.chunk_type().to_string(), String::from("FrSt"));
+    }
+
+    #[test]
+    fn test_encode_and_decode_message() {
+        let mut png = testing_png();
+        png.encode_message("ruSt", "hidden").unwrap();
+        assert_eq!(png.decode_message("ruSt"), Some(String::from("hidden")));
+    }
+
+    #[test]
+    fn test_as_bytes_round_trip() {
+        let png = testing_png();
+        let bytes = png.as_bytes();
+        let decoded = Png::from_bytes(bytes.as_ref()).unwrap();
+        assert_eq!(bytes, decoded.as_bytes());
+    }
+}